@@ -1,7 +1,13 @@
 use ldap3::{LdapConn, LdapResult, SearchEntry, Scope};
+use ldap3::controls::{ControlParser, ControlType, PagedResults};
+use ldap3::exop::PasswordModify;
+use std::collections::BTreeMap;
 use std::convert::TryFrom;
 use std::fmt;
+use std::io::Write;
 use std::mem::swap;
+use std::net::ToSocketAddrs;
+use std::path::Path;
 use serde::{Serialize, Deserialize};
 
 /// Rsults produced by the crate
@@ -28,39 +34,104 @@ pub struct User {
     /// CSE group memberships
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub cse_groups: Vec<String>,
+    /// Any requested LDAP attribute this crate doesn't otherwise model,
+    /// e.g. `telephoneNumber` or `homeDirectory`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, Vec<String>>,
 }
 
 impl User {
-    /// Query a user from LDAP using their own credentials
+    /// Query a user from LDAP using their own credentials, the default
+    /// directory configuration, and every attribute this crate models
     pub fn query(zid: impl AsRef<str>, password: impl AsRef<str>) -> Result<Self> {
-        User::query_other(zid.as_ref(), password, zid.as_ref())
+        User::query_other(
+            zid.as_ref(),
+            password,
+            zid.as_ref(),
+            &Config::default(),
+            &Projection::default(),
+        )
     }
 
-    /// Query a user from LDAP using another user's credentials
+    /// Query a user from LDAP using another user's credentials, fetching
+    /// only the attributes named by `projection`
     pub fn query_other(
         auth_zid: impl AsRef<str>,
         password: impl AsRef<str>,
         subject_zid: impl AsRef<str>,
+        config: &Config,
+        projection: &Projection,
     ) -> Result<Self> {
 
-        let unsw = Conn::unsw(auth_zid, password)?;
+        let unsw = Conn::unsw(&config.unsw, &config.unsw_domain, auth_zid, password)?;
         let query = format!("(&(cn={})(objectClass=user))", subject_zid.as_ref());
         let unsw_user = unsw
-            .search::<UnswUser>(query)?
+            .search::<UnswUser>(query, projection)?
             .next()
             .ok_or(Error::InsufficientResults)
             .and_then(|user| user)?;
 
-        let cse = Conn::cse()?;
-        let query = format!("(&(cn={})(objectClass=account))", subject_zid.as_ref());
+        let cse = Conn::cse(&config.cse)?;
+        User::join(&cse, unsw_user, projection)
+    }
+
+    /// Search for every user matching a structured filter, using another
+    /// user's credentials to authenticate
+    ///
+    /// Unlike [`query_other`](User::query_other), which returns the single
+    /// user matching a zID, this joins the UNSW and CSE directories for
+    /// every match of `filter`.
+    pub fn search(
+        auth_zid: impl AsRef<str>,
+        password: impl AsRef<str>,
+        filter: UserFilter,
+        config: &Config,
+        projection: &Projection,
+    ) -> Result<Vec<User>> {
+        let cse = Conn::cse(&config.cse)?;
+        let unsw = Conn::unsw(&config.unsw, &config.unsw_domain, auth_zid, password)?;
+
+        let query = filter.unsw_filter(&cse)?;
+        unsw
+            .search::<UnswUser>(query, projection)?
+            .map(|unsw_user| User::join(&cse, unsw_user?, projection))
+            .collect()
+    }
+
+    /// Join a UNSW directory entry with its matching CSE account and group
+    /// memberships to produce a complete `User`
+    fn join(cse: &Conn, unsw_user: UnswUser, projection: &Projection) -> Result<Self> {
+        let query = format!("(&(cn={})(objectClass=account))", &unsw_user.name);
         let cse_user = cse
-            .search::<CseUser>(query)?
+            .search::<CseUser>(query, projection)?
+            .next()
+            .ok_or(Error::InsufficientResults)
+            .and_then(|user| user)?;
+        User::combine(cse, unsw_user, cse_user)
+    }
+
+    /// Join a CSE account with its matching UNSW directory entry and group
+    /// memberships to produce a complete `User`, the same as `join` but
+    /// starting from the CSE side of the directory
+    fn join_from_cse(
+        unsw: &Conn,
+        cse: &Conn,
+        cse_user: CseUser,
+        projection: &Projection,
+    ) -> Result<Self> {
+        let query = format!("(&(cn={})(objectClass=user))", &cse_user.item.cn);
+        let unsw_user = unsw
+            .search::<UnswUser>(query, projection)?
             .next()
             .ok_or(Error::InsufficientResults)
             .and_then(|user| user)?;
+        User::combine(cse, unsw_user, cse_user)
+    }
+
+    fn combine(cse: &Conn, unsw_user: UnswUser, cse_user: CseUser) -> Result<Self> {
         let query = format!("(&(member={})(objectClass=groupOfNames))", &cse_user.item.dn);
         let groups = cse
-            .search::<CseGroup>(query)?
+            .search::<CseGroup>(query, &Projection::default())?
             .flat_map(|group| group.ok());
 
         let zid = unsw_user.name;
@@ -71,39 +142,498 @@ impl User {
         let department = unsw_user.department;
         let cse_groups = groups.map(|group| group.item.cn).collect();
 
-        Ok(User { zid, name, email, aliases, company, department, cse_groups })
+        let mut extra = unsw_user.extra;
+        extra.extend(cse_user.extra);
+
+        Ok(User { zid, name, email, aliases, company, department, cse_groups, extra })
+    }
+
+    /// Export every user in the directory as newline-delimited JSON,
+    /// paginating the underlying CSE search so the whole directory is never
+    /// held in memory at once
+    ///
+    /// Entries that fail to resolve (e.g. a CSE account with no matching
+    /// UNSW entry) are skipped and logged to stderr with their zID, rather
+    /// than aborting the whole export.
+    pub fn export(
+        auth_zid: impl AsRef<str>,
+        password: impl AsRef<str>,
+        config: &Config,
+        projection: &Projection,
+        mut out: impl Write,
+    ) -> Result<()> {
+        let unsw = Conn::unsw(&config.unsw, &config.unsw_domain, auth_zid, password)?;
+        let cse = Conn::cse(&config.cse)?;
+
+        let query = "(objectClass=account)".to_owned();
+        for cse_user in cse.search_paged::<CseUser>(query, EXPORT_PAGE_SIZE, projection) {
+            let cse_user = match cse_user {
+                Ok(cse_user) => cse_user,
+                Err(error) => {
+                    eprintln!("cse-query: skipping unreadable CSE entry: {}", error);
+                    continue;
+                }
+            };
+            let zid = cse_user.item.cn.clone();
+
+            match User::join_from_cse(&unsw, &cse, cse_user, projection) {
+                Ok(user) => {
+                    serde_json::to_writer(&mut out, &user)?;
+                    writeln!(out)?;
+                }
+                Err(error) => eprintln!("cse-query: skipping {}: {}", zid, error),
+            }
+        }
+
+        Ok(())
     }
+
+    /// Rotate a user's CSE password via the RFC 3062 Password Modify
+    /// extended operation, over a connection bound with the old password
+    pub fn change_password(
+        auth_zid: impl AsRef<str>,
+        old_password: impl AsRef<str>,
+        new_password: impl AsRef<str>,
+        config: &Config,
+    ) -> Result<()> {
+        let cse = Conn::cse_as(&config.cse, auth_zid.as_ref(), old_password.as_ref())?;
+
+        let modify = PasswordModify {
+            user_id: Some(auth_zid.as_ref()),
+            old_pass: Some(old_password.as_ref()),
+            new_pass: Some(new_password.as_ref()),
+        };
+        cse.conn.extended(modify)?.success()?;
+
+        Ok(())
+    }
+}
+
+/// A CSE group, with its resolved membership
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Group {
+    /// Distinguished name
+    pub dn: String,
+    /// Common name
+    pub cn: String,
+    /// Human-readable description, if the group has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// zIDs of the group's members that could be resolved back to a user
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<String>,
+}
+
+impl Group {
+    /// Query a CSE group and resolve its membership down to zIDs
+    pub fn query(
+        auth_zid: impl AsRef<str>,
+        password: impl AsRef<str>,
+        group_cn: impl AsRef<str>,
+        config: &Config,
+    ) -> Result<Self> {
+        let cse = Conn::cse_as(&config.cse, auth_zid, password)?;
+        let query = format!("(&(cn={})(objectClass=groupOfNames))", group_cn.as_ref());
+        let group = cse
+            .search::<GroupMembers>(query, &Projection::default())?
+            .next()
+            .ok_or(Error::InsufficientResults)
+            .and_then(|group| group)?;
+
+        Ok(Group::from_members(&cse, group))
+    }
+
+    /// Export every group in the directory as newline-delimited JSON,
+    /// paginating the underlying CSE search so the whole directory is never
+    /// held in memory at once
+    pub fn export(
+        auth_zid: impl AsRef<str>,
+        password: impl AsRef<str>,
+        config: &Config,
+        mut out: impl Write,
+    ) -> Result<()> {
+        let cse = Conn::cse_as(&config.cse, auth_zid, password)?;
+
+        let query = "(objectClass=groupOfNames)".to_owned();
+        for group in cse.search_paged::<GroupMembers>(query, EXPORT_PAGE_SIZE, &Projection::default()) {
+            let group = match group {
+                Ok(group) => group,
+                Err(error) => {
+                    eprintln!("cse-query: skipping unreadable CSE group: {}", error);
+                    continue;
+                }
+            };
+            let cn = group.item.cn.clone();
+            let group = Group::from_members(&cse, group);
+
+            if let Err(error) = serde_json::to_writer(&mut out, &group).map_err(Error::from) {
+                eprintln!("cse-query: skipping group {}: {}", cn, error);
+                continue;
+            }
+            writeln!(out)?;
+        }
+
+        Ok(())
+    }
+
+    /// Build a `Group` from its raw membership, resolving each member's DN
+    /// back down to a zID where possible
+    fn from_members(cse: &Conn, group: GroupMembers) -> Self {
+        let members = group.members
+            .iter()
+            .filter_map(|dn| Group::resolve_member(cse, dn).ok())
+            .collect();
+
+        Group {
+            dn: group.item.dn,
+            cn: group.item.cn,
+            description: group.description,
+            members,
+        }
+    }
+
+    /// Resolve a member's DN back down to their zID, where possible
+    fn resolve_member(cse: &Conn, dn: &str) -> Result<String> {
+        cse
+            .search_at::<CseUser>(dn, Scope::Base, "(objectClass=*)".to_owned(), &Projection::default())?
+            .next()
+            .ok_or(Error::InsufficientResults)
+            .and_then(|user| user)
+            .map(|user| user.item.cn)
+    }
+}
+
+/// A structured filter used to select multiple users at once
+///
+/// Each variant compiles down to an LDAP filter string the same way the
+/// hand-written queries on [`User`] do, except that [`Group`](UserFilter::Group)
+/// and [`Uid`](UserFilter::Uid) require first resolving group membership or
+/// login aliases against the CSE directory.
+#[derive(Debug, Clone)]
+pub enum UserFilter {
+    /// Users whose common name contains this substring
+    Name(String),
+    /// The exact CSE uid (login alias) of a user
+    Uid(String),
+    /// Members of a named CSE group
+    Group(String),
+    /// Users in a faculty or department containing this substring
+    Department(String),
+    /// Every sub-filter must match
+    All(Vec<UserFilter>),
+}
+
+impl UserFilter {
+    /// Compile this filter into an LDAP filter string for the UNSW directory
+    fn unsw_filter(&self, cse: &Conn) -> Result<String> {
+        let mut terms = String::new();
+        self.push_unsw_terms(cse, &mut terms)?;
+        Ok(format!("(&(objectClass=user){})", terms))
+    }
+
+    fn push_unsw_terms(&self, cse: &Conn, terms: &mut String) -> Result<()> {
+        match self {
+            UserFilter::Name(name) => terms.push_str(&format!("(cn=*{}*)", name)),
+            UserFilter::Uid(uid) => {
+                let zids = UserFilter::cse_uid_zids(cse, uid)?;
+                terms.push_str(&UserFilter::zid_filter(&zids));
+            }
+            UserFilter::Department(department) => {
+                terms.push_str(&format!("(|(company=*{0}*)(department=*{0}*))", department))
+            }
+            UserFilter::Group(group) => {
+                let zids = UserFilter::cse_group_members(cse, group)?;
+                terms.push_str(&UserFilter::zid_filter(&zids));
+            }
+            UserFilter::All(filters) => {
+                for filter in filters {
+                    filter.push_unsw_terms(cse, terms)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolve a CSE login alias (uid) down to the zID(s) of the accounts
+    /// that use it, since a user's uid and zID may differ
+    fn cse_uid_zids(cse: &Conn, uid: &str) -> Result<Vec<String>> {
+        let query = format!("(&(uid={})(objectClass=account))", uid);
+        cse.search::<CseUser>(query, &Projection::default())?
+            .map(|user| user.map(|user| user.item.cn))
+            .collect()
+    }
+
+    /// Resolve a CSE group's membership down to the zIDs of its members,
+    /// tolerating the same stale (unresolvable) member DNs that
+    /// [`Group::from_members`] does
+    fn cse_group_members(cse: &Conn, group_cn: &str) -> Result<Vec<String>> {
+        let query = format!("(&(cn={})(objectClass=groupOfNames))", group_cn);
+        let group = cse
+            .search::<GroupMembers>(query, &Projection::default())?
+            .next()
+            .ok_or(Error::InsufficientResults)
+            .and_then(|group| group)?;
+
+        Ok(group.members
+            .iter()
+            .filter_map(|dn| Group::resolve_member(cse, dn).ok())
+            .collect())
+    }
+
+    /// Compile a set of zIDs into an LDAP `OR` filter matching any of them,
+    /// or a filter that never matches if the set is empty, since `(|)` is
+    /// not valid LDAP filter syntax
+    fn zid_filter(zids: &[String]) -> String {
+        if zids.is_empty() {
+            "(!(objectClass=*))".to_owned()
+        } else {
+            let matches: String = zids.iter().map(|zid| format!("(cn={})", zid)).collect();
+            format!("(|{})", matches)
+        }
+    }
+}
+
+/// A set of LDAP attributes a caller wants fetched and emitted
+///
+/// This lets queries avoid pulling attributes nobody asked for, and lets
+/// callers request attributes this crate doesn't otherwise model — those
+/// land in [`User::extra`] instead of being dropped.
+#[derive(Debug, Clone)]
+pub enum Projection {
+    /// Fetch every attribute this crate knows how to model; the default
+    All,
+    /// Fetch only the attributes named here, in addition to whichever
+    /// attributes a response type always requires to populate its
+    /// mandatory fields
+    Only(Vec<String>),
+}
+
+impl Default for Projection {
+    fn default() -> Self {
+        Projection::All
+    }
+}
+
+impl Projection {
+    fn attrs_for<R: Response>(&self) -> Vec<&str> {
+        match self {
+            Projection::All => {
+                R::REQUIRED.iter().copied().chain(R::OPTIONAL.iter().copied()).collect()
+            }
+            Projection::Only(wanted) => {
+                let mut attrs: Vec<&str> = R::REQUIRED.iter().copied().collect();
+                for attr in wanted {
+                    if !attrs.contains(&attr.as_str()) {
+                        attrs.push(attr.as_str());
+                    }
+                }
+                attrs
+            }
+        }
+    }
+}
+
+/// Endpoint configuration for the directories the crate queries
+///
+/// Loaded by [`Config::load`] by layering, in increasing precedence, the
+/// built-in defaults, a TOML file, environment variables, and explicit CLI
+/// overrides.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    /// The UNSW-wide Active Directory server
+    pub unsw: DirectoryConfig,
+    /// Domain suffix appended to a username when binding to the UNSW
+    /// directory, e.g. `z1234567@ad.unsw.edu.au`
+    pub unsw_domain: String,
+    /// The CSE school's own directory server
+    pub cse: DirectoryConfig,
+}
+
+/// The URL and search base of a single LDAP directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectoryConfig {
+    /// LDAP URL of the directory server
+    pub url: String,
+    /// Base DN to search from
+    pub base: String,
 }
 
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            unsw: DirectoryConfig {
+                url: "ldaps://ad.unsw.edu.au/".to_owned(),
+                base: "OU=IDM,DC=ad,DC=unsw,DC=edu,DC=au".to_owned(),
+            },
+            unsw_domain: "ad.unsw.edu.au".to_owned(),
+            cse: DirectoryConfig {
+                url: "ldaps://bandleader.cse.unsw.edu.au/".to_owned(),
+                base: "dc=cse,dc=unsw,dc=edu,dc=au".to_owned(),
+            },
+        }
+    }
+}
+
+impl Config {
+    /// Load configuration, applying (in increasing precedence) a TOML file,
+    /// the `CSE_QUERY_*` environment variables, and explicit CLI overrides
+    /// on top of the built-in defaults
+    pub fn load(file: Option<&Path>, overrides: ConfigOverrides) -> Result<Self> {
+        let mut config = match file {
+            Some(path) => {
+                let contents = std::fs::read_to_string(path)?;
+                toml::from_str(&contents)?
+            }
+            None => Config::default(),
+        };
+
+        if let Ok(url) = std::env::var("CSE_QUERY_UNSW_URL") {
+            config.unsw.url = url;
+        }
+        if let Ok(base) = std::env::var("CSE_QUERY_UNSW_BASE") {
+            config.unsw.base = base;
+        }
+        if let Ok(domain) = std::env::var("CSE_QUERY_UNSW_DOMAIN") {
+            config.unsw_domain = domain;
+        }
+        if let Ok(url) = std::env::var("CSE_QUERY_CSE_URL") {
+            config.cse.url = url;
+        }
+        if let Ok(base) = std::env::var("CSE_QUERY_CSE_BASE") {
+            config.cse.base = base;
+        }
+
+        if let Some(url) = overrides.unsw_url {
+            config.unsw.url = url;
+        }
+        if let Some(base) = overrides.unsw_base {
+            config.unsw.base = base;
+        }
+        if let Some(domain) = overrides.unsw_domain {
+            config.unsw_domain = domain;
+        }
+        if let Some(url) = overrides.cse_url {
+            config.cse.url = url;
+        }
+        if let Some(base) = overrides.cse_base {
+            config.cse.base = base;
+        }
+
+        Ok(config)
+    }
+}
+
+/// Explicit, highest-precedence overrides for [`Config::load`], typically
+/// sourced from CLI flags
+#[derive(Debug, Default)]
+pub struct ConfigOverrides {
+    pub unsw_url: Option<String>,
+    pub unsw_base: Option<String>,
+    pub unsw_domain: Option<String>,
+    pub cse_url: Option<String>,
+    pub cse_base: Option<String>,
+}
+
+/// `(scheme prefix, default port)` pairs tried in order when resolving a
+/// directory URL's host
+const PROTOCOLS: &[(&str, u16)] = &[("ldaps://", 636), ("ldap://", 389)];
+
+/// Check that a directory URL's host resolves before attempting to bind, so
+/// a DNS failure is reported clearly instead of surfacing as a raw I/O error
+/// deep inside the LDAP handshake
+fn check_connectivity(url: &str) -> Result<()> {
+    for (scheme, default_port) in PROTOCOLS {
+        if let Some(host) = url.strip_prefix(scheme) {
+            let host = host.trim_end_matches('/');
+
+            if host.to_socket_addrs().is_ok() {
+                return Ok(());
+            }
+
+            let host_and_port = format!("{}:{}", host, default_port);
+            return host_and_port
+                .to_socket_addrs()
+                .map(|_| ())
+                .map_err(|_| Error::UnresolvedHost(host.to_owned()));
+        }
+    }
+
+    Err(Error::UnresolvedHost(url.to_owned()))
+}
+
+/// Default page size used by [`User::export`] and [`Group::export`]
+const EXPORT_PAGE_SIZE: i32 = 500;
+
 struct Conn {
-    base: &'static str,
+    base: String,
     conn: LdapConn,
 }
 
 impl Conn {
-    fn unsw(username: impl AsRef<str>, password: impl AsRef<str>) -> Result<Self> {
-        let url = "ldaps://ad.unsw.edu.au/";
-        let base = "OU=IDM,DC=ad,DC=unsw,DC=edu,DC=au";
-        let conn = LdapConn::new(url)?;
-        let username = format!("{}@ad.unsw.edu.au", username.as_ref());
+    fn unsw(
+        config: &DirectoryConfig,
+        domain: &str,
+        username: impl AsRef<str>,
+        password: impl AsRef<str>,
+    ) -> Result<Self> {
+        check_connectivity(&config.url)?;
+        let conn = LdapConn::new(&config.url)?;
+        let username = format!("{}@{}", username.as_ref(), domain);
         conn.simple_bind(&username, password.as_ref())?.success()?;
-        Ok(Conn { base, conn })
+        Ok(Conn { base: config.base.clone(), conn })
+    }
+
+    fn cse(config: &DirectoryConfig) -> Result<Self> {
+        check_connectivity(&config.url)?;
+        let conn = LdapConn::new(&config.url)?;
+        Ok(Conn { base: config.base.clone(), conn })
     }
 
-    fn cse() -> Result<Self> {
-        let url = "ldaps://bandleader.cse.unsw.edu.au/";
-        let base = "dc=cse,dc=unsw,dc=edu,dc=au";
-        let conn = LdapConn::new(url)?;
-        Ok(Conn { base, conn })
+    /// Bind to the CSE directory using a user's own credentials, rather than
+    /// the anonymous bind used for read-only lookups
+    fn cse_as(
+        config: &DirectoryConfig,
+        username: impl AsRef<str>,
+        password: impl AsRef<str>,
+    ) -> Result<Self> {
+        let conn = Conn::cse(config)?;
+        let bind_dn = format!("uid={},ou=People,{}", username.as_ref(), config.base);
+        conn.conn.simple_bind(&bind_dn, password.as_ref())?.success()?;
+        Ok(conn)
     }
 
     fn search<R: Response>(
         &self,
         filter: String,
+        projection: &Projection,
     ) -> Result<impl Iterator<Item = Result<R>>> {
-        let attrs = R::ATTRS.as_ref().iter().collect::<Vec<_>>();
+        self.search_at(&self.base, Scope::Subtree, filter, projection)
+    }
+
+    /// Run a search over the whole matching set using the LDAP simple
+    /// paged-results control, fetching one page of `page_size` entries at a
+    /// time rather than buffering the entire result set up front
+    fn search_paged<R: Response>(
+        &self,
+        filter: String,
+        page_size: i32,
+        projection: &Projection,
+    ) -> PagedSearch<'_, R> {
+        PagedSearch::new(self, filter, page_size, projection.clone())
+    }
+
+    /// Run a search rooted at an arbitrary base and scope, rather than the
+    /// directory's default subtree, e.g. to fetch a single entry by DN
+    fn search_at<R: Response>(
+        &self,
+        base: &str,
+        scope: Scope,
+        filter: String,
+        projection: &Projection,
+    ) -> Result<impl Iterator<Item = Result<R>>> {
+        let attrs = projection.attrs_for::<R>();
         let (results, _) = self.conn
-            .search(self.base, Scope::Subtree, filter.as_ref(), attrs)?
+            .search(base, scope, filter.as_ref(), attrs)?
             .success()?;
         let results = results
             .into_iter()
@@ -114,8 +644,85 @@ impl Conn {
     }
 }
 
+/// An iterator over a search driven by the LDAP simple paged-results
+/// control, fetching a new page only once the current one is exhausted
+struct PagedSearch<'a, R> {
+    conn: &'a Conn,
+    filter: String,
+    page_size: i32,
+    projection: Projection,
+    cookie: Vec<u8>,
+    done: bool,
+    page: std::vec::IntoIter<Result<R>>,
+}
+
+impl<'a, R: Response> PagedSearch<'a, R> {
+    fn new(conn: &'a Conn, filter: String, page_size: i32, projection: Projection) -> Self {
+        PagedSearch {
+            conn,
+            filter,
+            page_size,
+            projection,
+            cookie: Vec::new(),
+            done: false,
+            page: Vec::new().into_iter(),
+        }
+    }
+
+    fn fetch_page(&mut self) -> Result<()> {
+        let attrs = self.projection.attrs_for::<R>();
+        let paging = PagedResults { size: self.page_size, cookie: self.cookie.clone() };
+        let (entries, result) = self.conn.conn
+            .with_controls(paging.into())
+            .search(&self.conn.base, Scope::Subtree, self.filter.as_ref(), attrs)?
+            .success()?;
+
+        self.cookie = result.ctrls
+            .iter()
+            .find(|control| matches!(control.0, Some(ControlType::PagedResults)))
+            .and_then(|control| control.1.val.as_ref())
+            .map(|val| PagedResults::parse(val).cookie)
+            .unwrap_or_default();
+        self.done = self.cookie.is_empty();
+
+        self.page = entries
+            .into_iter()
+            .map(SearchEntry::construct)
+            .map(Deconstructor)
+            .map(TryFrom::try_from)
+            .collect::<Vec<_>>()
+            .into_iter();
+
+        Ok(())
+    }
+}
+
+impl<'a, R: Response> Iterator for PagedSearch<'a, R> {
+    type Item = Result<R>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entry) = self.page.next() {
+                return Some(entry);
+            }
+            if self.done {
+                return None;
+            }
+            if let Err(error) = self.fetch_page() {
+                self.done = true;
+                return Some(Err(error));
+            }
+        }
+    }
+}
+
 trait Response: TryFrom<Deconstructor, Error = Error> {
-    const ATTRS: &'static [&'static str];
+    /// Attributes always fetched for this response, needed to populate its
+    /// mandatory fields
+    const REQUIRED: &'static [&'static str];
+    /// Attributes fetched only when the caller's [`Projection`] asks for
+    /// everything this crate knows how to model
+    const OPTIONAL: &'static [&'static str] = &[];
 }
 
 struct Deconstructor(SearchEntry);
@@ -145,6 +752,25 @@ impl Deconstructor {
             .remove(name)
             .ok_or(Error::AttributeMissing(name))
     }
+
+    fn maybe_take_all(&mut self, name: &'static str) -> Vec<String> {
+        self.0.attrs.remove(name).unwrap_or_default()
+    }
+
+    /// Take the `cn`/`dn` pair without consuming the rest of the entry, so
+    /// any remaining attributes can still be drained with
+    /// [`take_remaining`](Deconstructor::take_remaining)
+    fn take_item(&mut self) -> Result<LdapItem> {
+        let dn = self.take_dn();
+        let cn = self.take_one("cn")?;
+        Ok(LdapItem { dn, cn })
+    }
+
+    /// Drain whatever attributes are left once every attribute a response
+    /// type models has already been taken, for [`User::extra`]
+    fn take_remaining(&mut self) -> BTreeMap<String, Vec<String>> {
+        self.0.attrs.drain().collect()
+    }
 }
 
 /// An item in an LDAP server
@@ -157,7 +783,7 @@ struct LdapItem {
 }
 
 impl Response for LdapItem {
-    const ATTRS: &'static [&'static str] = &["cn", "dn"];
+    const REQUIRED: &'static [&'static str] = &["cn", "dn"];
 }
 
 impl TryFrom<Deconstructor> for LdapItem {
@@ -177,7 +803,7 @@ struct CseGroup {
 }
 
 impl Response for CseGroup {
-    const ATTRS: &'static [&'static str] = &["cn", "dn"];
+    const REQUIRED: &'static [&'static str] = &["cn", "dn"];
 }
 
 impl TryFrom<Deconstructor> for CseGroup {
@@ -189,24 +815,51 @@ impl TryFrom<Deconstructor> for CseGroup {
     }
 }
 
+/// A CSE group's membership, as recorded by the CSE LDAP server
+#[derive(Debug)]
+struct GroupMembers {
+    item: LdapItem,
+    description: Option<String>,
+    members: Vec<String>,
+}
+
+impl Response for GroupMembers {
+    const REQUIRED: &'static [&'static str] = &["cn", "dn", "description", "member"];
+}
+
+impl TryFrom<Deconstructor> for GroupMembers {
+    type Error = Error;
+
+    fn try_from(mut entry: Deconstructor) -> Result<Self> {
+        let description = entry.maybe_take_one("description");
+        let members = entry.take_all("member")?;
+        let item = LdapItem::try_from(entry)?;
+        Ok(GroupMembers { item, description, members })
+    }
+}
+
 /// A user as recoded by the CSE LDAP server
 #[derive(Debug)]
 struct CseUser {
     item: LdapItem,
     uids: Vec<String>,
+    /// Any requested attribute this crate doesn't otherwise model
+    extra: BTreeMap<String, Vec<String>>,
 }
 
 impl Response for CseUser {
-    const ATTRS: &'static [&'static str] = &["cn", "dn", "uid"];
+    const REQUIRED: &'static [&'static str] = &["cn", "dn"];
+    const OPTIONAL: &'static [&'static str] = &["uid"];
 }
 
 impl TryFrom<Deconstructor> for CseUser {
     type Error = Error;
 
     fn try_from(mut entry: Deconstructor) -> Result<Self> {
-        let uids = entry.take_all("uid")?;
-        let item = LdapItem::try_from(entry)?;
-        Ok(CseUser { item, uids })
+        let uids = entry.maybe_take_all("uid");
+        let item = entry.take_item()?;
+        let extra = entry.take_remaining();
+        Ok(CseUser { item, uids, extra })
     }
 }
 
@@ -224,15 +877,13 @@ struct UnswUser {
     name: String,
     /// Email address
     mail: String,
+    /// Any requested attribute this crate doesn't otherwise model
+    extra: BTreeMap<String, Vec<String>>,
 }
 
 impl Response for UnswUser {
-    const ATTRS: &'static [&'static str] = &[
-        "cn", "dn",
-        "company", "department",
-        "displayName", "name",
-        "mail",
-    ];
+    const REQUIRED: &'static [&'static str] = &["cn", "dn", "displayName", "name", "mail"];
+    const OPTIONAL: &'static [&'static str] = &["company", "department"];
 }
 
 impl TryFrom<Deconstructor> for UnswUser {
@@ -244,8 +895,9 @@ impl TryFrom<Deconstructor> for UnswUser {
         let display_name = entry.take_one("displayName")?;
         let name = entry.take_one("name")?;
         let mail = entry.take_one("mail")?;
-        let item = LdapItem::try_from(entry)?;
-        Ok(UnswUser { item, company, department, display_name, name, mail })
+        let item = entry.take_item()?;
+        let extra = entry.take_remaining();
+        Ok(UnswUser { item, company, department, display_name, name, mail, extra })
     }
 }
 
@@ -258,9 +910,16 @@ pub enum Error {
     InvalidCredentials,
     /// An attribute was missing from a search result
     AttributeMissing(&'static str),
+    /// A command requires an authenticated zID, but none was given
+    AuthRequired,
+    /// A directory's configured host could not be resolved
+    UnresolvedHost(String),
+    /// A password change was rejected by the directory's password policy
+    PasswordPolicyViolation,
     Ldap(LdapResult),
     Io(std::io::Error),
     Json(serde_json::Error),
+    Toml(toml::de::Error),
 }
 
 impl fmt::Display for Error {
@@ -270,9 +929,13 @@ impl fmt::Display for Error {
             InsufficientResults => write!(f, "No results were provided for the search"),
             InvalidCredentials => write!(f, "Invalid user credentials"),
             AttributeMissing(attr) => write!(f, "Response was missing attribute: {}", attr),
+            AuthRequired => write!(f, "The --user flag is required to authenticate this command"),
+            UnresolvedHost(host) => write!(f, "Could not resolve directory host: {}", host),
+            PasswordPolicyViolation => write!(f, "The new password does not meet the password policy"),
             Ldap(error) => write!(f, "{}", error),
             Io(error) => write!(f, "{}", error),
             Json(error) => write!(f, "{}", error),
+            Toml(error) => write!(f, "{}", error),
         }
     }
 }
@@ -283,6 +946,7 @@ impl From<LdapResult> for Error {
     fn from(error: LdapResult) -> Self {
         match error {
             LdapResult { rc: 49, .. } => Error::InvalidCredentials,
+            LdapResult { rc: 53, .. } => Error::PasswordPolicyViolation,
             error => Error::Ldap(error),
         }
     }
@@ -299,3 +963,9 @@ impl From<serde_json::Error> for Error {
         Error::Json(error)
     }
 }
+
+impl From<toml::de::Error> for Error {
+    fn from(error: toml::de::Error) -> Self {
+        Error::Toml(error)
+    }
+}
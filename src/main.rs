@@ -2,6 +2,7 @@ use structopt::StructOpt;
 use dialoguer::Password;
 use serde_json::to_writer_pretty;
 use std::io::stdout;
+use std::path::PathBuf;
 
 use cse_query::*;
 
@@ -14,25 +15,146 @@ struct Args {
     /// Password to use to authenticate (rather than prompting)
     #[structopt(short, long)]
     password: Option<String>,
-    /// CSE user to query
-    user: String,
+    /// Load directory endpoint configuration from a TOML file
+    #[structopt(long, parse(from_os_str))]
+    config: Option<PathBuf>,
+    /// Override the UNSW directory's LDAP URL
+    #[structopt(long)]
+    unsw_url: Option<String>,
+    /// Override the UNSW directory's search base
+    #[structopt(long)]
+    unsw_base: Option<String>,
+    /// Override the domain suffix used to bind to the UNSW directory
+    #[structopt(long)]
+    unsw_domain: Option<String>,
+    /// Override the CSE directory's LDAP URL
+    #[structopt(long)]
+    cse_url: Option<String>,
+    /// Override the CSE directory's search base
+    #[structopt(long)]
+    cse_base: Option<String>,
+    /// Only fetch and emit these LDAP attributes, plus whichever are always
+    /// needed to fill a result's mandatory fields; CSE group memberships are
+    /// always included, and unmodeled attributes are emitted under `extra`
+    #[structopt(long, use_delimiter = true)]
+    attrs: Option<Vec<String>>,
+    #[structopt(subcommand)]
+    command: Command,
+}
+
+#[derive(StructOpt)]
+enum Command {
+    /// Look up a single user by zID
+    Query {
+        /// CSE user to query
+        user: String,
+    },
+    /// Search for every user matching a filter
+    Search {
+        /// Match users whose name contains this substring
+        #[structopt(long)]
+        name: Option<String>,
+        /// Match the exact CSE uid of a user
+        #[structopt(long)]
+        uid: Option<String>,
+        /// Match members of a named CSE group
+        #[structopt(long)]
+        group: Option<String>,
+        /// Match users in a faculty or department containing this substring
+        #[structopt(long)]
+        department: Option<String>,
+    },
+    /// Look up a CSE group and its resolved membership
+    Group {
+        /// Name of the group to query
+        name: String,
+    },
+    /// Rotate your own CSE password
+    Passwd,
+    /// Dump the directory to stdout as newline-delimited JSON, for loading
+    /// into other systems
+    Export {
+        /// Also export every CSE group and its resolved membership
+        #[structopt(long)]
+        groups: bool,
+    },
 }
 
 fn main() -> Result<()> {
     let args = Args::from_args();
 
-    let auth_user = args.auth_user.as_ref().unwrap_or(&args.user);
     let password = args.password.map(Ok).unwrap_or_else(|| {
         let mut password = Password::new();
         password.with_prompt("Enter LDAP password");
         password.interact()
     })?;
 
-    let user = User::query_other(auth_user, password, &args.user)?;
+    let config = Config::load(args.config.as_deref(), ConfigOverrides {
+        unsw_url: args.unsw_url,
+        unsw_base: args.unsw_base,
+        unsw_domain: args.unsw_domain,
+        cse_url: args.cse_url,
+        cse_base: args.cse_base,
+    })?;
+
+    let projection = match args.attrs {
+        Some(attrs) => Projection::Only(attrs),
+        None => Projection::All,
+    };
+
+    match args.command {
+        Command::Query { user } => {
+            let auth_user = args.auth_user.as_ref().unwrap_or(&user);
+            let user = User::query_other(auth_user, password, &user, &config, &projection)?;
+            to_writer_pretty(stdout(), &user)?;
+            println!();
+        }
+        Command::Search { name, uid, group, department } => {
+            let auth_user = args.auth_user.ok_or(Error::AuthRequired)?;
+
+            let mut filters = Vec::new();
+            if let Some(name) = name {
+                filters.push(UserFilter::Name(name));
+            }
+            if let Some(uid) = uid {
+                filters.push(UserFilter::Uid(uid));
+            }
+            if let Some(group) = group {
+                filters.push(UserFilter::Group(group));
+            }
+            if let Some(department) = department {
+                filters.push(UserFilter::Department(department));
+            }
+
+            let users = User::search(auth_user, password, UserFilter::All(filters), &config, &projection)?;
+            to_writer_pretty(stdout(), &users)?;
+            println!();
+        }
+        Command::Group { name } => {
+            let auth_user = args.auth_user.ok_or(Error::AuthRequired)?;
+            let group = Group::query(auth_user, password, name, &config)?;
+            to_writer_pretty(stdout(), &group)?;
+            println!();
+        }
+        Command::Passwd => {
+            let auth_user = args.auth_user.ok_or(Error::AuthRequired)?;
+            let new_password = Password::new()
+                .with_prompt("Enter new LDAP password")
+                .with_confirmation("Confirm new LDAP password", "Passwords did not match")
+                .interact()?;
 
-    to_writer_pretty(stdout(), &user)?;
+            User::change_password(auth_user, password, new_password, &config)?;
+            println!("Password updated");
+        }
+        Command::Export { groups } => {
+            let auth_user = args.auth_user.ok_or(Error::AuthRequired)?;
 
-    println!();
+            User::export(&auth_user, &password, &config, &projection, stdout())?;
+            if groups {
+                Group::export(&auth_user, &password, &config, stdout())?;
+            }
+        }
+    }
 
     Ok(())
 }